@@ -3,18 +3,61 @@
 #[ink::contract]
 pub mod magink {
     use crate::ensure;
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::DefaultEnvironment;
     use ink::storage::Mapping;
+    use ink::prelude::vec::Vec;
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
         TooEarlyToClaim,
         UserNotFound,
+        NotOwner,
+        CollectionNotSet,
+        MintFailed,
+        EraOutOfBounds,
+        Paused,
+        NotAllowed,
+        InsufficientFee,
+        InsufficientTreasury,
+        Reentrant,
     }
 
     #[ink(storage)]
     pub struct Magink {
         user: Mapping<AccountId, Profile>,
+        /// Account allowed to configure the contract.
+        owner: AccountId,
+        /// PSP34 NFT collection minted to on each successful claim.
+        collection: Option<AccountId>,
+        /// Token ids minted to each account via `claim`.
+        badge_tokens: Mapping<AccountId, Vec<u32>>,
+        /// Next token id to mint in the collection.
+        next_token_id: u32,
+        /// Smallest era accepted by `start`.
+        min_era: u8,
+        /// Largest era accepted by `start`.
+        max_era: u8,
+        /// When `true`, `claim` is rejected for everyone.
+        paused: bool,
+        /// When `true`, only accounts in `allowed` may `start`/`claim`.
+        allowlist_enabled: bool,
+        /// Accounts permitted to `start`/`claim` while the allowlist is enabled.
+        allowed: Mapping<AccountId, ()>,
+        /// Fixed fee required to call `claim`.
+        claim_fee: Balance,
+        /// Fees collected from `claim` calls, withdrawable by the owner.
+        treasury: Balance,
+        /// Accounts that have ever called `start`, in registration order.
+        participants: Vec<AccountId>,
+        /// `(account, badges_claimed)` for every participant, kept sorted by badge
+        /// count descending so `leaderboard` can page through it without scanning
+        /// or re-sorting on every read.
+        ranking: Vec<(AccountId, u8)>,
+        /// Set for the duration of a `claim` call, guarding against the registered
+        /// `collection` reentering `claim` from its `mint` callback.
+        claim_in_progress: bool,
     }
 
     #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, scale::Encode, scale::Decode,)]
@@ -26,39 +69,322 @@ pub mod magink {
         start_block: u32,
         // number of badges claimed
         badges_claimed: u8,
+        // head of the tamper-evident claim hashchain
+        claim_hash: [u8; 32],
     }
 
     impl Magink {
-        /// Creates a new Magink smart contract.
+        /// Creates a new Magink smart contract, charging `claim_fee` per `claim`.
         #[ink(constructor)]
-        pub fn new() -> Self {
+        pub fn new(claim_fee: Balance) -> Self {
             Self {
                 user: Mapping::new(),
+                owner: Self::env().caller(),
+                collection: None,
+                badge_tokens: Mapping::new(),
+                next_token_id: 0,
+                min_era: 0,
+                max_era: u8::MAX,
+                paused: false,
+                allowlist_enabled: false,
+                allowed: Mapping::new(),
+                claim_fee,
+                treasury: 0,
+                participants: Vec::new(),
+                ranking: Vec::new(),
+                claim_in_progress: false,
             }
         }
 
+        /// Sets the fixed fee required to call `claim`. Owner-only.
+        #[ink(message)]
+        pub fn set_claim_fee(&mut self, claim_fee: Balance) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.claim_fee = claim_fee;
+            Ok(())
+        }
+
+        /// Pays out `amount` from the treasury to the caller. Owner-only.
+        #[ink(message)]
+        pub fn withdraw(&mut self, amount: Balance) -> Result<(), Error> {
+            self.ensure_owner()?;
+            ensure!(amount <= self.treasury, Error::InsufficientTreasury);
+            self.env()
+                .transfer(self.env().caller(), amount)
+                .map_err(|_| Error::InsufficientTreasury)?;
+            self.treasury -= amount;
+            Ok(())
+        }
+
+        /// Returns the fee required to call `claim`.
+        #[ink(message)]
+        pub fn get_claim_fee(&self) -> Balance {
+            self.claim_fee
+        }
+
+        /// Returns the accumulated treasury balance.
+        #[ink(message)]
+        pub fn get_treasury(&self) -> Balance {
+            self.treasury
+        }
+
+        /// Registers the PSP34 collection that badges are minted into. Owner-only.
+        #[ink(message)]
+        pub fn set_collection(&mut self, collection: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.collection = Some(collection);
+            Ok(())
+        }
+
+        /// Sets the smallest era accepted by `start`. Owner-only.
+        #[ink(message)]
+        pub fn set_min_era(&mut self, min_era: u8) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.min_era = min_era;
+            Ok(())
+        }
+
+        /// Sets the largest era accepted by `start`. Owner-only.
+        #[ink(message)]
+        pub fn set_max_era(&mut self, max_era: u8) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.max_era = max_era;
+            Ok(())
+        }
+
+        /// Pauses `claim` for everyone. Owner-only.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.paused = true;
+            Ok(())
+        }
+
+        /// Resumes `claim`. Owner-only.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.paused = false;
+            Ok(())
+        }
+
+        /// Enables or disables the claimant allowlist. Owner-only.
+        #[ink(message)]
+        pub fn set_allowlist_enabled(&mut self, enabled: bool) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.allowlist_enabled = enabled;
+            Ok(())
+        }
+
+        /// Adds `account` to the claimant allowlist. Owner-only.
+        #[ink(message)]
+        pub fn add_allowed(&mut self, account: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.allowed.insert(account, &());
+            Ok(())
+        }
+
+        /// Removes `account` from the claimant allowlist. Owner-only.
+        #[ink(message)]
+        pub fn remove_allowed(&mut self, account: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.allowed.remove(account);
+            Ok(())
+        }
+
         /// (Re)Start the Magink the claiming era for the caller.
         #[ink(message)]
-        pub fn start(&mut self, era: u8) {
+        pub fn start(&mut self, era: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+            self.ensure_allowed(caller)?;
+            ensure!(
+                era >= self.min_era && era <= self.max_era,
+                Error::EraOutOfBounds
+            );
+
+            if self.user.get(caller).is_none() {
+                self.participants.push(caller);
+            }
+
             let profile = Profile {
                 claim_era: era,
                 start_block: self.env().block_number(),
                 badges_claimed: 0,
+                claim_hash: [0u8; 32],
             };
-            self.user.insert(self.env().caller(), &profile);
+            self.user.insert(caller, &profile);
+            self.update_ranking(caller, 0);
+            Ok(())
+        }
+
+        /// Returns the total number of accounts that have ever called `start`.
+        #[ink(message)]
+        pub fn total_participants(&self) -> u32 {
+            self.participants.len() as u32
         }
 
-        /// Claim the badge after the era.
+        /// Returns a page of `(account, badges_claimed)` entries sorted by badge
+        /// count descending, starting at `offset` and containing at most `limit`
+        /// entries. `ranking` is kept sorted as badges are claimed, so this walks
+        /// only the first `offset + limit` entries and never re-sorts; the whole
+        /// `ranking` vector is still loaded with the contract's storage.
         #[ink(message)]
-        pub fn claim(&mut self) -> Result<(), Error> {
+        pub fn leaderboard(&self, offset: u32, limit: u32) -> Vec<(AccountId, u8)> {
+            self.ranking
+                .iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .copied()
+                .collect()
+        }
+
+        /// Repositions `account`'s entry in `ranking` to reflect its new badge
+        /// count, keeping the list sorted descending.
+        fn update_ranking(&mut self, account: AccountId, badges_claimed: u8) {
+            if let Some(pos) = self.ranking.iter().position(|(a, _)| *a == account) {
+                self.ranking.remove(pos);
+            }
+            let insert_at = self.ranking.partition_point(|(_, b)| *b > badges_claimed);
+            self.ranking.insert(insert_at, (account, badges_claimed));
+        }
+
+        fn ensure_owner(&self) -> Result<(), Error> {
+            ensure!(self.env().caller() == self.owner, Error::NotOwner);
+            Ok(())
+        }
+
+        fn ensure_allowed(&self, account: AccountId) -> Result<(), Error> {
+            ensure!(
+                !self.allowlist_enabled || self.allowed.contains(account),
+                Error::NotAllowed
+            );
+            Ok(())
+        }
+
+        /// Claim the badge after the era, minting a PSP34 token for the caller and
+        /// returning its token id. Requires paying at least `claim_fee`; the full
+        /// transferred value (including any overpayment) is credited to the
+        /// treasury on success, and refunded if the claim fails for any reason.
+        #[ink(message, payable)]
+        pub fn claim(&mut self) -> Result<u32, Error> {
+            ensure!(!self.claim_in_progress, Error::Reentrant);
+            let caller = self.env().caller();
+            let value = self.env().transferred_value();
+
+            self.claim_in_progress = true;
+            let result = self.claim_inner(caller, value);
+            self.claim_in_progress = false;
+
+            if result.is_err() && value > 0 {
+                // The runtime credits `value` to our balance as part of dispatching
+                // this call regardless of the return value, so a failed claim must
+                // refund it explicitly or it's stranded (unreachable via
+                // `withdraw`, which is bounded by `treasury`, not actual balance).
+                let _ = self.env().transfer(caller, value);
+            }
+            result
+        }
+
+        fn claim_inner(&mut self, caller: AccountId, value: Balance) -> Result<u32, Error> {
+            ensure!(!self.paused, Error::Paused);
+            self.ensure_allowed(caller)?;
             ensure!(self.get_remaining() == 0, Error::TooEarlyToClaim);
+            ensure!(self.collection.is_some(), Error::CollectionNotSet);
+            ensure!(value >= self.claim_fee, Error::InsufficientFee);
+
+            // Mint first: it's the one fallible step that doesn't trap on failure,
+            // so the fee and profile must not be committed until it succeeds. The
+            // `claim_in_progress` guard set by `claim` keeps this cross-contract
+            // call from reentering `claim` and minting again off the same,
+            // not-yet-advanced era.
+            let token_id = self.mint_badge(caller)?;
+
+            self.treasury += value;
 
-            // update profile
             let mut profile = self.get_profile().ok_or(Error::UserNotFound).unwrap();
             profile.badges_claimed += 1;
             profile.start_block = self.env().block_number();
-            self.user.insert(self.env().caller(), &profile);
-            Ok(())
+            profile.claim_hash = self.next_claim_hash(
+                profile.claim_hash,
+                caller,
+                profile.start_block,
+                profile.badges_claimed,
+            );
+            self.user.insert(caller, &profile);
+            self.update_ranking(caller, profile.badges_claimed);
+
+            Ok(token_id)
+        }
+
+        /// Returns the current head of the claim hashchain for the given account.
+        #[ink(message)]
+        pub fn get_claim_hash(&self, account: AccountId) -> [u8; 32] {
+            self.get_account_profile(account)
+                .map_or([0u8; 32], |profile| profile.claim_hash)
+        }
+
+        /// Recomputes the claim hashchain from the zero genesis against the supplied
+        /// `(block_number, badges_claimed)` history and checks it matches the stored head.
+        #[ink(message)]
+        pub fn verify_chain(&self, account: AccountId, claims: Vec<(u32, u8)>) -> bool {
+            let mut hash = [0u8; 32];
+            for (block_number, badges_claimed) in claims {
+                hash = self.next_claim_hash(hash, account, block_number, badges_claimed);
+            }
+            hash == self.get_claim_hash(account)
+        }
+
+        /// Folds one claim into the hashchain: `keccak256(prev_hash ++ caller ++ block_number_le ++ badges_claimed_le)`.
+        fn next_claim_hash(
+            &self,
+            prev_hash: [u8; 32],
+            caller: AccountId,
+            block_number: u32,
+            badges_claimed: u8,
+        ) -> [u8; 32] {
+            let mut input = Vec::with_capacity(32 + 32 + 4 + 1);
+            input.extend_from_slice(&prev_hash);
+            input.extend_from_slice(caller.as_ref());
+            input.extend_from_slice(&block_number.to_le_bytes());
+            input.extend_from_slice(&badges_claimed.to_le_bytes());
+
+            let mut output = [0u8; 32];
+            self.env()
+                .hash_bytes::<ink::env::hash::Keccak256>(&input, &mut output);
+            output
+        }
+
+        /// Returns the PSP34 token ids minted to `account` via `claim`.
+        #[ink(message)]
+        pub fn get_badge_tokens(&self, account: AccountId) -> Vec<u32> {
+            self.badge_tokens.get(account).unwrap_or_default()
+        }
+
+        /// Mints the next badge token id to `to` in the registered PSP34 collection.
+        fn mint_badge(&mut self, to: AccountId) -> Result<u32, Error> {
+            let collection = self.collection.ok_or(Error::CollectionNotSet)?;
+            let token_id = self.next_token_id;
+
+            build_call::<DefaultEnvironment>()
+                .call(collection)
+                .gas_limit(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "PSP34Mintable::mint"
+                    )))
+                    .push_arg(to)
+                    .push_arg(token_id),
+                )
+                .returns::<()>()
+                .try_invoke()
+                .map_err(|_| Error::MintFailed)?
+                .map_err(|_| Error::MintFailed)?;
+
+            self.next_token_id = token_id.wrapping_add(1);
+            let mut tokens = self.get_badge_tokens(to);
+            tokens.push(token_id);
+            self.badge_tokens.insert(to, &tokens);
+            Ok(token_id)
         }
 
         /// Returns the remaining blocks in the era.
@@ -121,9 +447,9 @@ pub mod magink {
 
         #[ink::test]
         fn start_works() {
-            let mut magink = Magink::new();
+            let mut magink = Magink::new(0);
             println!("get {:?}", magink.get_remaining());
-            magink.start(10);
+            assert_eq!(Ok(()), magink.start(10));
             assert_eq!(10, magink.get_remaining());
             advance_block();
             assert_eq!(9, magink.get_remaining());
@@ -133,36 +459,184 @@ pub mod magink {
         fn claim_works() {
             const ERA: u32 = 10;
             let accounts = default_accounts();
-            let mut magink = Magink::new();
-            magink.start(ERA as u8);
+            let mut magink = Magink::new(0);
+            assert_eq!(Ok(()), magink.start(ERA as u8));
             advance_n_blocks(ERA - 1);
             assert_eq!(1, magink.get_remaining());
 
             // claim fails, too early
             assert_eq!(Err(Error::TooEarlyToClaim), magink.claim());
-            
-            // claim succeeds
-            advance_block();
-            assert_eq!(Ok(()), magink.claim());
-            assert_eq!(1, magink.get_badges());
-            assert_eq!(1, magink.get_badges_for(accounts.alice));
-            assert_eq!(1, magink.get_badges());
-            assert_eq!(10, magink.get_remaining());
-            
-            // claim fails, too early
-            assert_eq!(Err(Error::TooEarlyToClaim), magink.claim());
+
+            // claim fails, no collection registered yet
             advance_block();
-            assert_eq!(9, magink.get_remaining());
-            assert_eq!(Err(Error::TooEarlyToClaim), magink.claim());
+            assert_eq!(Err(Error::CollectionNotSet), magink.claim());
+            assert_eq!(0, magink.get_badges());
+            assert!(magink.get_badge_tokens(accounts.alice).is_empty());
+        }
+
+        #[ink::test]
+        fn claim_hash_chain_works() {
+            // The mint call always fails in the offline unit-test environment (there's
+            // no real PSP34 collection to invoke), and `claim` no longer advances the
+            // hashchain unless the mint succeeds. So the chain folding itself is
+            // exercised directly here; the end-to-end "a successful claim advances the
+            // chain" path is covered by the e2e suite instead.
+            let accounts = default_accounts();
+            let magink = Magink::new(0);
+            assert_eq!([0u8; 32], magink.get_claim_hash(accounts.alice));
+
+            let first = magink.next_claim_hash([0u8; 32], accounts.alice, 10, 1);
+            let second = magink.next_claim_hash(first, accounts.alice, 20, 2);
+            assert_ne!([0u8; 32], first);
+            assert_ne!(first, second);
+
+            // an untouched account's genesis hash verifies against an empty history
+            assert!(magink.verify_chain(accounts.alice, ink::prelude::vec![]));
+            assert!(!magink.verify_chain(accounts.alice, ink::prelude::vec![(10, 1)]));
+        }
+
+        #[ink::test]
+        fn set_collection_is_owner_gated() {
+            let accounts = default_accounts();
+            let mut magink = Magink::new(0);
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                Err(Error::NotOwner),
+                magink.set_collection(accounts.django)
+            );
+
+            set_caller(accounts.alice);
+            assert_eq!(Ok(()), magink.set_collection(accounts.django));
+        }
+
+        #[ink::test]
+        fn era_bounds_are_enforced() {
+            let mut magink = Magink::new(0);
+            magink.set_min_era(5).unwrap();
+            magink.set_max_era(20).unwrap();
+
+            assert_eq!(Err(Error::EraOutOfBounds), magink.start(4));
+            assert_eq!(Err(Error::EraOutOfBounds), magink.start(21));
+            assert_eq!(Ok(()), magink.start(10));
+        }
+
+        #[ink::test]
+        fn pause_blocks_claim() {
+            let mut magink = Magink::new(0);
+            magink.set_collection(AccountId::from([0x01; 32])).unwrap();
+            magink.start(0).unwrap();
+            magink.pause().unwrap();
+
+            assert_eq!(Err(Error::Paused), magink.claim());
+
+            magink.unpause().unwrap();
+            assert_ne!(Err(Error::Paused), magink.claim());
+        }
+
+        #[ink::test]
+        fn allowlist_gates_start_and_claim() {
+            let accounts = default_accounts();
+            let mut magink = Magink::new(0);
+            magink.set_allowlist_enabled(true).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(Err(Error::NotAllowed), magink.start(1));
+
+            set_caller(accounts.alice);
+            magink.add_allowed(accounts.bob).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(Ok(()), magink.start(1));
+
+            // a non-listed account cannot claim either
+            set_caller(accounts.charlie);
+            assert_eq!(Err(Error::NotAllowed), magink.claim());
+        }
+
+        #[ink::test]
+        fn insufficient_claim_fee_is_rejected() {
+            // The cross-contract mint always fails in the offline unit-test
+            // environment, and the fee is only credited once the mint succeeds, so
+            // the "fee is collected on a successful claim" path is covered by the
+            // e2e suite. Here we only check that an insufficient fee is rejected and
+            // never collected.
+            let mut magink = Magink::new(100);
+            magink.set_collection(AccountId::from([0x01; 32])).unwrap();
+            magink.start(0).unwrap();
+
+            set_value_transferred(50);
+            assert_eq!(Err(Error::InsufficientFee), magink.claim());
+            assert_eq!(0, magink.get_treasury());
+        }
+
+        #[ink::test]
+        fn withdraw_is_owner_gated_and_bounded() {
+            let accounts = default_accounts();
+            let mut magink = Magink::new(100);
+
+            set_caller(accounts.bob);
+            assert_eq!(Err(Error::NotOwner), magink.withdraw(100));
+
+            set_caller(accounts.alice);
+            assert_eq!(Err(Error::InsufficientTreasury), magink.withdraw(1));
+        }
+
+        fn set_value_transferred(value: Balance) {
+            ink::env::test::set_value_transferred::<Environment>(value);
+        }
+
+        #[ink::test]
+        fn leaderboard_is_sorted_and_paginated() {
+            // `claim` always fails its cross-contract mint in the offline unit-test
+            // environment, so badge counts are seeded directly on the stored
+            // profiles (and `ranking` kept in sync via `update_ranking`, exactly as
+            // a successful `claim` would) rather than by driving real claims.
+            let accounts = default_accounts();
+            let mut magink = Magink::new(0);
+            assert_eq!(0, magink.total_participants());
+
+            set_caller(accounts.alice);
+            magink.start(0).unwrap();
+            let mut alice_profile = magink.get_account_profile(accounts.alice).unwrap();
+            alice_profile.badges_claimed = 1;
+            magink.user.insert(accounts.alice, &alice_profile);
+            magink.update_ranking(accounts.alice, alice_profile.badges_claimed);
+
+            set_caller(accounts.bob);
+            magink.start(0).unwrap();
+            let mut bob_profile = magink.get_account_profile(accounts.bob).unwrap();
+            bob_profile.badges_claimed = 2;
+            magink.user.insert(accounts.bob, &bob_profile);
+            magink.update_ranking(accounts.bob, bob_profile.badges_claimed);
+
+            // restarting an existing participant must not duplicate the entry
+            set_caller(accounts.alice);
+            magink.start(0).unwrap();
+
+            assert_eq!(2, magink.total_participants());
+            assert_eq!(
+                ink::prelude::vec![(accounts.bob, 2), (accounts.alice, 0)],
+                magink.leaderboard(0, 10)
+            );
+            assert_eq!(
+                ink::prelude::vec![(accounts.bob, 2)],
+                magink.leaderboard(0, 1)
+            );
+            assert_eq!(
+                ink::prelude::vec![(accounts.alice, 0)],
+                magink.leaderboard(1, 1)
+            );
         }
 
         fn default_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
             ink::env::test::default_accounts::<Environment>()
         }
 
-        // fn set_sender(sender: AccountId) {
-        //     ink::env::test::set_caller::<Environment>(sender);
-        // }
+        fn set_caller(sender: AccountId) {
+            ink::env::test::set_caller::<Environment>(sender);
+        }
+
         fn advance_n_blocks(n: u32) {
             for _ in 0..n {
                 advance_block();
@@ -176,89 +650,291 @@ pub mod magink {
     #[cfg(all(test, feature = "e2e-tests"))]
     mod e2e_tests {
         use super::*;
-        use ink::primitives::AccountId;
-        use ink_e2e::build_message;
-        
+        use ink_e2e::ContractsBackend;
+        use psp34_mock::psp34_mock::{Psp34Mock, Psp34MockRef};
+
         type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
-        
+
         const ERA: u8 = 10; // Era duration in blocks
-        
+
         #[ink_e2e::test]
         async fn start_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
-            let mut magink = MaginkRef::new(); // Instantiate Magink contract
-        
+            let mut constructor = MaginkRef::new(0);
+            let contract = client
+                .instantiate("magink", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Magink>();
+
             // Start the era for the caller
-            let start_message = build_message::<MaginkRef>(magink.account_id())
-                .call(|magink| magink.start(ERA));
+            let start = call_builder.start(ERA);
             client
-                .call(&ink_e2e::alice(), start_message, 0, None)
+                .call(&ink_e2e::alice(), &start)
+                .submit()
                 .await
-                .expect("start message failed");
-        
+                .expect("start message failed")
+                .return_value()
+                .expect("start returned an error");
+
             // Verify remaining blocks in the era
-            let remaining = magink.get_remaining();
-            assert_eq!(remaining, ERA);
-        
+            let get_remaining = call_builder.get_remaining();
+            let remaining = client
+                .call(&ink_e2e::alice(), &get_remaining)
+                .dry_run()
+                .await?;
+            assert_eq!(remaining.return_value(), ERA);
+
             // Advance the block and check remaining again
-            advance_block(&mut client, &magink).await;
-            let remaining_after_advance = magink.get_remaining();
-            assert_eq!(remaining_after_advance, ERA - 1);
-        
+            advance_block(&mut client).await;
+            let remaining_after_advance = client
+                .call(&ink_e2e::alice(), &get_remaining)
+                .dry_run()
+                .await?;
+            assert_eq!(remaining_after_advance.return_value(), ERA - 1);
+
             Ok(())
         }
-        
+
         #[ink_e2e::test]
-        async fn claim_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
-            let mut magink = MaginkRef::new(); // Instantiate Magink contract
-            magink.start(ERA); // Start the era
-        
-            advance_n_blocks(&mut client, &magink, ERA - 1).await; // Advance blocks
-        
-            // Claim badge fails, too early
-            let claim_result = magink.claim();
-            assert_eq!(claim_result, Err(Error::TooEarlyToClaim));
-        
-            // Advance the block and claim the badge
-            advance_block(&mut client, &magink).await;
-            let claim_result = magink.claim();
-            assert_eq!(claim_result, Ok(()));
-        
-            // Verify badge count for the caller
-            let badge_count = magink.get_badges();
-            assert_eq!(badge_count, 1);
-        
-            // Verify badge count for Alice's account
-            let badge_count_for_alice = magink.get_badges_for(ink_e2e::alice());
-            assert_eq!(badge_count_for_alice, 1);
-        
-            // Verify remaining blocks after claiming
-            let remaining_after_claim = magink.get_remaining();
-            assert_eq!(remaining_after_claim, ERA);
-        
+        async fn claim_dry_run_reverts_before_era_elapses(
+            mut client: ink_e2e::Client<C, E>,
+        ) -> E2EResult<()> {
+            let mut collection_constructor = Psp34MockRef::new();
+            let collection = client
+                .instantiate("psp34_mock", &ink_e2e::alice(), &mut collection_constructor)
+                .submit()
+                .await
+                .expect("psp34_mock instantiate failed");
+
+            let mut constructor = MaginkRef::new(0);
+            let contract = client
+                .instantiate("magink", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Magink>();
+
+            let set_collection = call_builder.set_collection(collection.account_id);
+            client
+                .call(&ink_e2e::alice(), &set_collection)
+                .submit()
+                .await
+                .expect("set_collection message failed")
+                .return_value()
+                .expect("set_collection returned an error");
+
+            let start = call_builder.start(ERA);
+            client
+                .call(&ink_e2e::alice(), &start)
+                .submit()
+                .await
+                .expect("start message failed")
+                .return_value()
+                .expect("start returned an error");
+
+            // Dry-run the claim before the era elapses: it reverts with
+            // `TooEarlyToClaim` without submitting an extrinsic or consuming gas.
+            let claim = call_builder.claim();
+            let dry_run = client.call(&ink_e2e::alice(), &claim).dry_run().await?;
+            assert_eq!(dry_run.return_value(), Err(Error::TooEarlyToClaim));
+
+            // Advance past the era and submit the real extrinsic. The collection is
+            // registered above, so this mint actually succeeds.
+            advance_n_blocks(&mut client, ERA as u32).await;
+            let claim_result = client
+                .call(&ink_e2e::alice(), &claim)
+                .submit()
+                .await
+                .expect("claim message failed")
+                .return_value();
+            assert!(claim_result.is_ok());
+
+            // Verify the claiming era was reset for the caller.
+            let get_remaining = call_builder.get_remaining();
+            let remaining = client
+                .call(&ink_e2e::alice(), &get_remaining)
+                .dry_run()
+                .await?;
+            assert_eq!(remaining.return_value(), ERA);
+
             Ok(())
         }
-        
-        // Helper function to advance the block
-        async fn advance_block(
-            client: &mut ink_e2e::Client<C, E>,
-            contract: &MaginkRef,
-        ) {
-            let advance_message = build_message::<MaginkRef>(contract.account_id())
-                .call(|magink| magink.get_remaining());
+
+        #[ink_e2e::test]
+        async fn claim_mints_a_psp34_badge(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mut collection_constructor = Psp34MockRef::new();
+            let collection = client
+                .instantiate("psp34_mock", &ink_e2e::alice(), &mut collection_constructor)
+                .submit()
+                .await
+                .expect("psp34_mock instantiate failed");
+            let collection_call_builder = collection.call_builder::<Psp34Mock>();
+
+            let mut constructor = MaginkRef::new(0);
+            let contract = client
+                .instantiate("magink", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Magink>();
+
+            let set_collection = call_builder.set_collection(collection.account_id);
             client
-                .call(&ink_e2e::alice(), advance_message, 0, None)
+                .call(&ink_e2e::alice(), &set_collection)
+                .submit()
+                .await
+                .expect("set_collection message failed")
+                .return_value()
+                .expect("set_collection returned an error");
+
+            let start = call_builder.start(0);
+            client
+                .call(&ink_e2e::alice(), &start)
+                .submit()
+                .await
+                .expect("start message failed")
+                .return_value()
+                .expect("start returned an error");
+
+            let claim = call_builder.claim();
+            let token_id = client
+                .call(&ink_e2e::alice(), &claim)
+                .submit()
                 .await
-                .expect("advance block message failed");
+                .expect("claim message failed")
+                .return_value()
+                .expect("claim should mint successfully once a collection is registered");
+            assert_eq!(0, token_id);
+
+            // The badge was recorded on Magink's side...
+            let get_badge_tokens = call_builder.get_badge_tokens(ink_e2e::alice().account_id());
+            let badge_tokens = client
+                .call(&ink_e2e::alice(), &get_badge_tokens)
+                .dry_run()
+                .await?;
+            assert_eq!(badge_tokens.return_value(), ink::prelude::vec![token_id]);
+
+            // ...and the PSP34 collection actually minted it to the claimant.
+            let owner_of = collection_call_builder.owner_of(token_id);
+            let owner = client
+                .call(&ink_e2e::alice(), &owner_of)
+                .dry_run()
+                .await?;
+            assert_eq!(owner.return_value(), Some(ink_e2e::alice().account_id()));
+
+            // A successful claim also advances the caller's hashchain.
+            let get_claim_hash = call_builder.get_claim_hash(ink_e2e::alice().account_id());
+            let claim_hash = client
+                .call(&ink_e2e::alice(), &get_claim_hash)
+                .dry_run()
+                .await?;
+            assert_ne!(claim_hash.return_value(), [0u8; 32]);
+
+            Ok(())
         }
-        
-        // Helper function to advance n blocks
-        async fn advance_n_blocks(
-            client: &mut ink_e2e::Client<C, E>,
-            contract: &MaginkRef,
-            n: u8,
-        ) {
+
+        #[ink_e2e::test]
+        async fn claim_fee_accrues_to_treasury_and_is_withdrawable(
+            mut client: ink_e2e::Client<C, E>,
+        ) -> E2EResult<()> {
+            const CLAIM_FEE: Balance = 100;
+
+            let mut collection_constructor = Psp34MockRef::new();
+            let collection = client
+                .instantiate("psp34_mock", &ink_e2e::alice(), &mut collection_constructor)
+                .submit()
+                .await
+                .expect("psp34_mock instantiate failed");
+
+            let mut constructor = MaginkRef::new(CLAIM_FEE);
+            let contract = client
+                .instantiate("magink", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("instantiate failed");
+            let mut call_builder = contract.call_builder::<Magink>();
+
+            let set_collection = call_builder.set_collection(collection.account_id);
+            client
+                .call(&ink_e2e::alice(), &set_collection)
+                .submit()
+                .await
+                .expect("set_collection message failed")
+                .return_value()
+                .expect("set_collection returned an error");
+
+            let start = call_builder.start(0);
+            client
+                .call(&ink_e2e::alice(), &start)
+                .submit()
+                .await
+                .expect("start message failed")
+                .return_value()
+                .expect("start returned an error");
+
+            let claim = call_builder.claim();
+            client
+                .call(&ink_e2e::alice(), &claim)
+                .value(CLAIM_FEE)
+                .submit()
+                .await
+                .expect("claim message failed")
+                .return_value()
+                .expect("claim should mint successfully once a collection is registered");
+
+            let get_treasury = call_builder.get_treasury();
+            let treasury = client
+                .call(&ink_e2e::alice(), &get_treasury)
+                .dry_run()
+                .await?;
+            assert_eq!(treasury.return_value(), CLAIM_FEE);
+
+            let withdraw = call_builder.withdraw(CLAIM_FEE);
+            client
+                .call(&ink_e2e::alice(), &withdraw)
+                .submit()
+                .await
+                .expect("withdraw message failed")
+                .return_value()
+                .expect("withdraw returned an error");
+
+            let treasury_after_withdraw = client
+                .call(&ink_e2e::alice(), &get_treasury)
+                .dry_run()
+                .await?;
+            assert_eq!(treasury_after_withdraw.return_value(), 0);
+
+            // The treasury is now empty, so any further withdrawal is rejected.
+            let withdraw_again = call_builder.withdraw(1);
+            let withdraw_again_result = client
+                .call(&ink_e2e::alice(), &withdraw_again)
+                .dry_run()
+                .await?;
+            assert_eq!(
+                withdraw_again_result.return_value(),
+                Err(Error::InsufficientTreasury)
+            );
+
+            Ok(())
+        }
+
+        // Advances the chain by one block. A dev node produces a block per submitted
+        // extrinsic, so we submit a single harmless extrinsic (instantiating a
+        // throwaway contract) to move it forward.
+        async fn advance_block(client: &mut ink_e2e::Client<C, E>) {
+            let mut constructor = MaginkRef::new(0);
+            client
+                .instantiate("magink", &ink_e2e::bob(), &mut constructor)
+                .submit()
+                .await
+                .expect("advance block instantiate failed");
+        }
+
+        // Advances the chain by `n` blocks.
+        async fn advance_n_blocks(client: &mut ink_e2e::Client<C, E>, n: u32) {
             for _ in 0..n {
-                advance_block(client, contract).await;
+                advance_block(client).await;
             }
         }
     }
@@ -0,0 +1,35 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// Minimal PSP34 collection used to exercise Magink's cross-contract mint call in
+/// e2e tests. Only implements the `mint` message Magink depends on, plus an
+/// `owner_of` query the tests use to check the minted token's owner.
+#[ink::contract]
+pub mod psp34_mock {
+    use ink::storage::Mapping;
+
+    #[ink(storage)]
+    pub struct Psp34Mock {
+        owner_of: Mapping<u32, AccountId>,
+    }
+
+    impl Psp34Mock {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                owner_of: Mapping::new(),
+            }
+        }
+
+        /// `PSP34Mintable::mint`, selector-pinned to match the call Magink makes in
+        /// `Magink::mint_badge`.
+        #[ink(message, selector = 0x6c41f2ec)]
+        pub fn mint(&mut self, account: AccountId, id: u32) {
+            self.owner_of.insert(id, &account);
+        }
+
+        #[ink(message)]
+        pub fn owner_of(&self, id: u32) -> Option<AccountId> {
+            self.owner_of.get(id)
+        }
+    }
+}